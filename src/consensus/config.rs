@@ -1,5 +1,6 @@
 //! Configuration types for the Malachite consensus engine
 
+use eyre::Result;
 use malachitebft_app::{
     config::{
         ConsensusConfig as MalachiteConsensusConfig, DiscoveryConfig, LoggingConfig, MetricsConfig,
@@ -8,44 +9,99 @@ use malachitebft_app::{
     node::NodeConfig as MalachiteNodeConfig,
 };
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// Main configuration for the consensus node
+///
+/// Deserialized directly from the node's TOML config file, so every section
+/// understood by `malachitebft_app::config` (timeouts, value payload mode,
+/// discovery, runtime, logging, value sync, ...) round-trips without any
+/// manual field plucking.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeConfig {
     /// Node moniker (human-readable name)
+    #[serde(default)]
     pub moniker: String,
     /// Consensus configuration
     pub consensus: MalachiteConsensusConfig,
     /// Metrics configuration
     pub metrics: MetricsConfig,
     /// Runtime configuration
+    #[serde(default)]
     pub runtime: RuntimeConfig,
     /// Logging configuration
+    #[serde(default)]
     pub logging: LoggingConfig,
     /// Value synchronization configuration
+    #[serde(default)]
     pub value_sync: ValueSyncConfig,
+    /// Height-activated schedule of consensus parameter changes
+    #[serde(default)]
+    pub fork_schedule: ForkSchedule,
+    /// Tunables for the parallel value-sync catch-up driver
+    #[serde(default)]
+    pub catchup: CatchupConfig,
+    /// Base data directory override (defaults to `~/.tempo/<chain_id>` when absent).
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+}
+
+/// Tunables for the parallel value-sync catch-up driver.
+///
+/// Lets operators trade bandwidth for catch-up speed when a node joins at a
+/// low `start_height`: `worker_count` bounds how many decided-value range
+/// fetches are in flight at once, and `query_interval_ms` paces how quickly
+/// new fetches are dispatched.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CatchupConfig {
+    /// Maximum number of concurrent decided-value range fetches.
+    pub worker_count: usize,
+    /// Minimum delay, in milliseconds, between dispatching successive fetches.
+    pub query_interval_ms: u64,
+}
+
+impl Default for CatchupConfig {
+    fn default() -> Self {
+        Self {
+            worker_count: 4,
+            query_interval_ms: 50,
+        }
+    }
 }
 
 impl NodeConfig {
-    /// Create a new node configuration with default values
-    pub fn new(moniker: String, listen_addr: String, peers: Vec<String>) -> Self {
-        let listen_addr = multiaddr::Multiaddr::from_str(&listen_addr)
-            .unwrap_or_else(|_| "/ip4/127.0.0.1/tcp/26656".parse().unwrap());
+    /// Create a new node configuration with default values.
+    ///
+    /// Returns an error instead of silently falling back to a default
+    /// listen address or dropping unparseable peers, so a typo in a
+    /// multiaddr is never lost.
+    pub fn new(moniker: String, listen_addr: String, peers: Vec<String>) -> Result<Self> {
+        let parsed_listen_addr = multiaddr::Multiaddr::from_str(&listen_addr)
+            .map_err(|e| eyre::eyre!("invalid listen address '{listen_addr}': {e}"))?;
 
         let persistent_peers = peers
             .into_iter()
-            .filter_map(|p| multiaddr::Multiaddr::from_str(&p).ok())
-            .collect();
+            .map(|p| {
+                multiaddr::Multiaddr::from_str(&p)
+                    .map_err(|e| eyre::eyre!("invalid peer address '{p}': {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        Self {
+        Ok(Self {
             moniker,
             consensus: MalachiteConsensusConfig {
                 value_payload: ValuePayload::ProposalAndParts,
                 timeouts: TimeoutConfig::default(),
                 p2p: P2pConfig {
                     protocol: PubSubProtocol::default(),
-                    listen_addr,
+                    listen_addr: parsed_listen_addr,
                     persistent_peers,
                     discovery: DiscoveryConfig {
                         enabled: false,
@@ -61,7 +117,127 @@ impl NodeConfig {
             runtime: RuntimeConfig::default(),
             logging: LoggingConfig::default(),
             value_sync: ValueSyncConfig::default(),
+            fork_schedule: ForkSchedule::default(),
+            catchup: CatchupConfig::default(),
+            data_dir: None,
+        })
+    }
+
+    /// Effective consensus configuration at the given height.
+    ///
+    /// Folds every fork in `fork_schedule` whose `activation_height` is
+    /// `<= height` onto the base `consensus` config, in activation order, so
+    /// callers always see the parameters that apply at that height without
+    /// needing a binary restart when a fork activates.
+    pub fn consensus_at(&self, height: crate::Height) -> Cow<'_, MalachiteConsensusConfig> {
+        let active = self.fork_schedule.active_as_of(height);
+        if active.is_empty() {
+            return Cow::Borrowed(&self.consensus);
+        }
+
+        let mut config = self.consensus.clone();
+        for fork in active {
+            fork.overrides.apply(&mut config);
+        }
+        Cow::Owned(config)
+    }
+}
+
+/// A single scheduled change to consensus parameters, activating at `activation_height`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Fork {
+    /// Height at which `overrides` starts applying.
+    pub activation_height: crate::Height,
+    /// Parameters to override once this fork is active.
+    pub overrides: ConsensusParamsOverride,
+}
+
+/// Subset of [`MalachiteConsensusConfig`] that a [`Fork`] may override.
+///
+/// Fields left `None` keep whatever the base config (or an earlier fork)
+/// already set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConsensusParamsOverride {
+    /// Override the value payload mode.
+    #[serde(default)]
+    pub value_payload: Option<ValuePayload>,
+    /// Override the round timeouts.
+    #[serde(default)]
+    pub timeouts: Option<TimeoutConfig>,
+    /// Override the pubsub protocol.
+    #[serde(default)]
+    pub pubsub_protocol: Option<PubSubProtocol>,
+}
+
+impl ConsensusParamsOverride {
+    fn apply(&self, config: &mut MalachiteConsensusConfig) {
+        if let Some(value_payload) = self.value_payload {
+            config.value_payload = value_payload;
+        }
+        if let Some(timeouts) = self.timeouts.clone() {
+            config.timeouts = timeouts;
+        }
+        if let Some(protocol) = self.pubsub_protocol {
+            config.p2p.protocol = protocol;
+        }
+    }
+}
+
+/// Height-activated schedule of consensus parameter changes.
+///
+/// Forks must be strictly increasing in `activation_height`; this is
+/// enforced both by [`ForkSchedule::new`] and when deserializing from TOML,
+/// so the binary-search in [`ForkSchedule::active_as_of`] is always sound.
+/// Serializes back to the same bare array it deserializes from, so a
+/// `NodeConfig` round-trips through TOML.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(try_from = "Vec<Fork>")]
+pub struct ForkSchedule {
+    forks: Vec<Fork>,
+}
+
+impl ForkSchedule {
+    /// Build a fork schedule, rejecting a non-strictly-increasing height order.
+    pub fn new(forks: Vec<Fork>) -> Result<Self> {
+        forks.try_into().map_err(|e: String| eyre::eyre!(e))
+    }
+
+    /// Forks whose `activation_height` is `<= height`, in activation order.
+    fn active_as_of(&self, height: crate::Height) -> &[Fork] {
+        let idx = self
+            .forks
+            .partition_point(|fork| fork.activation_height <= height);
+        &self.forks[..idx]
+    }
+}
+
+impl TryFrom<Vec<Fork>> for ForkSchedule {
+    type Error = String;
+
+    fn try_from(forks: Vec<Fork>) -> std::result::Result<Self, Self::Error> {
+        for pair in forks.windows(2) {
+            if pair[0].activation_height >= pair[1].activation_height {
+                return Err(
+                    "fork schedule must be strictly increasing in activation_height".to_string(),
+                );
+            }
         }
+        Ok(Self { forks })
+    }
+}
+
+impl From<ForkSchedule> for Vec<Fork> {
+    fn from(schedule: ForkSchedule) -> Self {
+        schedule.forks
+    }
+}
+
+impl Serialize for ForkSchedule {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.forks.serialize(serializer)
     }
 }
 
@@ -134,6 +310,29 @@ impl NetworkConfig {
     }
 }
 
+/// Retention policy for decided values and undecided proposals.
+///
+/// `keep_decided_heights` bounds how many of the most recent decided heights
+/// the store retains (`None` keeps everything); `prune_interval` controls how
+/// many heights elapse between pruning passes so long-running validators
+/// don't accumulate unbounded table growth.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Number of most-recent decided heights to keep. `None` retains all of them.
+    pub keep_decided_heights: Option<u64>,
+    /// How many heights to advance between pruning passes.
+    pub prune_interval: crate::Height,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_decided_heights: None,
+            prune_interval: crate::Height::default(),
+        }
+    }
+}
+
 /// Engine configuration combining all settings
 #[derive(Clone, Debug)]
 pub struct EngineConfig {
@@ -143,44 +342,393 @@ pub struct EngineConfig {
     pub wal: WalConfig,
     /// Network configuration
     pub network: NetworkConfig,
+    /// Base data directory all node state is rooted under
+    pub data_dir: DataDir,
+    /// Pruning / retention policy for the store
+    pub retention: RetentionConfig,
     /// Height to start from (if resuming)
     pub start_height: Option<crate::Height>,
 }
 
 impl EngineConfig {
-    /// Create a new engine configuration
-    pub fn new(chain_id: String, moniker: String, listen_addr: SocketAddr) -> Self {
+    /// Create a new engine configuration.
+    ///
+    /// Returns `Result` because it now goes through [`NodeConfig::new`] and
+    /// [`EngineConfigBuilder::build`], both of which validate rather than
+    /// silently default; there are no other call sites of `EngineConfig::new`
+    /// in this crate to update for the signature change.
+    pub fn new(chain_id: String, moniker: String, listen_addr: SocketAddr) -> Result<Self> {
         let listen_str = format!("/ip4/{}/tcp/{}", listen_addr.ip(), listen_addr.port());
-        let network = NetworkConfig::new(chain_id, listen_addr);
-        let node = NodeConfig::new(moniker, listen_str, Vec::new());
+        let node = NodeConfig::new(moniker, listen_str, Vec::new())?;
 
-        Self {
-            node,
-            wal: WalConfig::default(),
-            network,
-            start_height: None,
-        }
+        EngineConfigBuilder::new()
+            .chain_id(chain_id)
+            .node(node)
+            .build()
     }
 
-    /// Set the WAL directory
+    /// Set the WAL directory directly, bypassing the data directory.
     pub fn with_wal_dir(mut self, path: PathBuf) -> Self {
         self.wal.path = path;
         self
     }
 
+    /// Relocate all node state under `data_dir`, re-basing the WAL path (and
+    /// any future per-network subdirectory) beneath it. A single call moves
+    /// the whole node's on-disk footprint.
+    pub fn with_data_dir(mut self, data_dir: PathBuf) -> Result<Self> {
+        self.data_dir = DataDir::resolve(&self.network.chain_id, Some(data_dir))?;
+        self.wal.path = self.data_dir.subdir("wal")?;
+        Ok(self)
+    }
+
     /// Set the starting height
     pub fn with_start_height(mut self, height: crate::Height) -> Self {
         self.start_height = Some(height);
         self
     }
 
-    /// Add peer addresses
-    pub fn with_peers(mut self, peers: Vec<String>) -> Self {
-        self.network.peers = peers.clone();
-        self.node.consensus.p2p.persistent_peers = peers
-            .into_iter()
-            .filter_map(|p| multiaddr::Multiaddr::from_str(&p).ok())
-            .collect();
+    /// Override the pruning / retention policy
+    pub fn with_retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Add peer addresses.
+    ///
+    /// Returns an error instead of silently dropping peers that fail to
+    /// parse as multiaddrs, and re-runs the same duplicate/self-listen-address
+    /// checks as [`EngineConfigBuilder::build`] so this can't be used to
+    /// reintroduce a peer the builder would have rejected.
+    pub fn with_peers(mut self, peers: Vec<String>) -> Result<Self> {
+        let parsed_peers = peers
+            .iter()
+            .map(|p| {
+                multiaddr::Multiaddr::from_str(p)
+                    .map_err(|e| eyre::eyre!("invalid peer address '{p}': {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        validate_persistent_peers(&self.node.consensus.p2p.listen_addr, &parsed_peers)?;
+
+        self.network.peers = peers;
+        self.node.consensus.p2p.persistent_peers = parsed_peers;
+        Ok(self)
+    }
+}
+
+/// Resolves the on-disk layout for a node's persistent state.
+///
+/// Defaults to `~/.tempo/<chain_id>`, overridable by an explicit base
+/// directory (the `data_dir` TOML key / `--data-dir` CLI flag), and derives
+/// every per-component subdirectory (WAL, store, ...) beneath it. Resolving
+/// the base path is pure — nothing is created on disk until a subdirectory
+/// is actually requested via [`DataDir::subdir`]. [`EngineConfigBuilder::build`]
+/// requests the `wal` subdirectory (creating it) unless an explicit
+/// [`WalConfig`] was supplied.
+#[derive(Clone, Debug)]
+pub struct DataDir {
+    base: PathBuf,
+}
+
+impl DataDir {
+    /// Resolve the base data directory for `chain_id`, honoring an explicit
+    /// override. Does not touch the filesystem.
+    pub fn resolve(chain_id: &str, data_dir_override: Option<PathBuf>) -> Result<Self> {
+        let base = match data_dir_override {
+            Some(dir) => dir,
+            None => dirs::home_dir()
+                .ok_or_else(|| eyre::eyre!("could not determine home directory"))?
+                .join(".tempo")
+                .join(chain_id),
+        };
+
+        Ok(Self { base })
+    }
+
+    /// The base directory itself.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Derive (and create) a subdirectory beneath the base directory.
+    pub fn subdir(&self, name: &str) -> Result<PathBuf> {
+        let path = self.base.join(name);
+        fs::create_dir_all(&path)?;
+        Ok(path)
+    }
+}
+
+/// Builder for [`EngineConfig`], following the validate-at-`build()` pattern:
+/// every multiaddr is parsed and checked for duplicates and self-references
+/// before the final config is produced, instead of being silently dropped or
+/// defaulted.
+#[derive(Debug, Default)]
+pub struct EngineConfigBuilder {
+    chain_id: Option<String>,
+    node: Option<NodeConfig>,
+    wal: Option<WalConfig>,
+    data_dir_override: Option<PathBuf>,
+    retention: Option<RetentionConfig>,
+    start_height: Option<crate::Height>,
+}
+
+impl EngineConfigBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the chain ID the engine is running for.
+    pub fn chain_id(mut self, chain_id: String) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Set the node configuration (consensus, metrics, runtime, logging, value sync).
+    pub fn node(mut self, node: NodeConfig) -> Self {
+        self.node = Some(node);
+        self
+    }
+
+    /// Override the default WAL configuration.
+    pub fn wal(mut self, wal: WalConfig) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
+    /// Override the base data directory (defaults to `~/.tempo/<chain_id>`,
+    /// or the node config's own `data_dir` field if that's set). Takes
+    /// precedence over both.
+    pub fn data_dir(mut self, data_dir: PathBuf) -> Self {
+        self.data_dir_override = Some(data_dir);
+        self
+    }
+
+    /// Set the height to resume from.
+    pub fn start_height(mut self, height: crate::Height) -> Self {
+        self.start_height = Some(height);
+        self
+    }
+
+    /// Override the default pruning / retention policy.
+    pub fn retention(mut self, retention: RetentionConfig) -> Self {
+        self.retention = Some(retention);
         self
     }
+
+    /// Validate and assemble the final [`EngineConfig`].
+    ///
+    /// Rejects a persistent peer that duplicates another peer, a persistent
+    /// peer equal to the node's own listen address, and a listen address
+    /// that doesn't resolve to a concrete `ip4`/`ip6` + `tcp` socket address.
+    pub fn build(self) -> Result<EngineConfig> {
+        let chain_id = self
+            .chain_id
+            .ok_or_else(|| eyre::eyre!("EngineConfigBuilder: missing chain_id"))?;
+        let node = self
+            .node
+            .ok_or_else(|| eyre::eyre!("EngineConfigBuilder: missing node configuration"))?;
+
+        let listen_addr = node.consensus.p2p.listen_addr.clone();
+        validate_persistent_peers(&listen_addr, &node.consensus.p2p.persistent_peers)?;
+
+        let listen_socket_addr = multiaddr_to_socket_addr(&listen_addr)?;
+        let network = NetworkConfig::new(chain_id, listen_socket_addr).with_peers(
+            node.consensus
+                .p2p
+                .persistent_peers
+                .iter()
+                .map(|peer| peer.to_string())
+                .collect(),
+        );
+
+        let data_dir_override = self.data_dir_override.or_else(|| node.data_dir.clone());
+        let data_dir = DataDir::resolve(&network.chain_id, data_dir_override)?;
+        let wal = match self.wal {
+            Some(wal) => wal,
+            None => WalConfig {
+                path: data_dir.subdir("wal")?,
+                ..WalConfig::default()
+            },
+        };
+
+        Ok(EngineConfig {
+            node,
+            wal,
+            network,
+            data_dir,
+            retention: self.retention.unwrap_or_default(),
+            start_height: self.start_height,
+        })
+    }
+}
+
+/// Reject a persistent peer that duplicates another peer or that equals
+/// `listen_addr`. Shared by [`EngineConfigBuilder::build`] and
+/// [`EngineConfig::with_peers`] so neither path can reintroduce a
+/// duplicate/self peer that the other already guards against.
+fn validate_persistent_peers(
+    listen_addr: &multiaddr::Multiaddr,
+    peers: &[multiaddr::Multiaddr],
+) -> Result<()> {
+    let mut seen_peers = HashSet::with_capacity(peers.len());
+    for peer in peers {
+        if peer == listen_addr {
+            return Err(eyre::eyre!(
+                "persistent peer '{peer}' is the node's own listen address"
+            ));
+        }
+        if !seen_peers.insert(peer) {
+            return Err(eyre::eyre!("duplicate persistent peer: '{peer}'"));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a `Multiaddr` down to the concrete `ip4`/`ip6` + `tcp` socket
+/// address it describes, rejecting anything that lacks either component.
+fn multiaddr_to_socket_addr(addr: &multiaddr::Multiaddr) -> Result<SocketAddr> {
+    use multiaddr::Protocol;
+
+    let mut ip = None;
+    let mut port = None;
+
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::Ip4(ip4) => ip = Some(std::net::IpAddr::V4(ip4)),
+            Protocol::Ip6(ip6) => ip = Some(std::net::IpAddr::V6(ip6)),
+            Protocol::Tcp(p) => port = Some(p),
+            _ => {}
+        }
+    }
+
+    match (ip, port) {
+        (Some(ip), Some(port)) => Ok(SocketAddr::new(ip, port)),
+        _ => Err(eyre::eyre!(
+            "multiaddr '{addr}' must contain an ip4/ip6 address and a tcp port"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fork(activation_height: u64) -> Fork {
+        Fork {
+            activation_height: crate::Height::from(activation_height),
+            overrides: ConsensusParamsOverride::default(),
+        }
+    }
+
+    #[test]
+    fn fork_schedule_accepts_strictly_increasing_heights() {
+        let schedule = ForkSchedule::new(vec![fork(10), fork(20), fork(30)]);
+        assert!(schedule.is_ok());
+    }
+
+    #[test]
+    fn fork_schedule_rejects_non_increasing_heights() {
+        assert!(ForkSchedule::new(vec![fork(10), fork(10)]).is_err());
+        assert!(ForkSchedule::new(vec![fork(20), fork(10)]).is_err());
+    }
+
+    #[test]
+    fn fork_schedule_active_as_of_only_includes_reached_forks() {
+        let schedule = ForkSchedule::new(vec![fork(10), fork(20)]).unwrap();
+
+        assert!(schedule.active_as_of(crate::Height::from(5)).is_empty());
+        assert_eq!(schedule.active_as_of(crate::Height::from(10)).len(), 1);
+        assert_eq!(schedule.active_as_of(crate::Height::from(25)).len(), 2);
+    }
+
+    fn test_data_dir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "tempo-config-test-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ))
+    }
+
+    #[test]
+    fn builder_rejects_self_referencing_peer() {
+        let node = NodeConfig::new(
+            "node".to_string(),
+            "/ip4/127.0.0.1/tcp/30000".to_string(),
+            vec!["/ip4/127.0.0.1/tcp/30000".to_string()],
+        )
+        .unwrap();
+
+        let result = EngineConfigBuilder::new()
+            .chain_id("test".to_string())
+            .node(node)
+            .data_dir(test_data_dir())
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_rejects_duplicate_peers() {
+        let node = NodeConfig::new(
+            "node".to_string(),
+            "/ip4/127.0.0.1/tcp/30000".to_string(),
+            vec![
+                "/ip4/127.0.0.1/tcp/30001".to_string(),
+                "/ip4/127.0.0.1/tcp/30001".to_string(),
+            ],
+        )
+        .unwrap();
+
+        let result = EngineConfigBuilder::new()
+            .chain_id("test".to_string())
+            .node(node)
+            .data_dir(test_data_dir())
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_accepts_distinct_peers() {
+        let node = NodeConfig::new(
+            "node".to_string(),
+            "/ip4/127.0.0.1/tcp/30000".to_string(),
+            vec![
+                "/ip4/127.0.0.1/tcp/30001".to_string(),
+                "/ip4/127.0.0.1/tcp/30002".to_string(),
+            ],
+        )
+        .unwrap();
+
+        let result = EngineConfigBuilder::new()
+            .chain_id("test".to_string())
+            .node(node)
+            .data_dir(test_data_dir())
+            .build();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn with_peers_rejects_what_build_would_reject() {
+        let node = NodeConfig::new(
+            "node".to_string(),
+            "/ip4/127.0.0.1/tcp/30000".to_string(),
+            Vec::new(),
+        )
+        .unwrap();
+
+        let config = EngineConfigBuilder::new()
+            .chain_id("test".to_string())
+            .node(node)
+            .data_dir(test_data_dir())
+            .build()
+            .unwrap();
+
+        assert!(config
+            .with_peers(vec!["/ip4/127.0.0.1/tcp/30000".to_string()])
+            .is_err());
+    }
 }
@@ -0,0 +1,21 @@
+//! Persistent storage for consensus decided values and undecided proposals,
+//! backed by Reth's database.
+
+mod reth_store;
+mod wrapper;
+
+pub use reth_store::RethStore;
+pub use wrapper::{Store, ValueSyncDriver};
+
+use crate::{context::MalachiteContext, Value};
+use malachitebft_core_types::CommitCertificate;
+use serde::{Deserialize, Serialize};
+
+/// A decided value together with the certificate that committed it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecidedValue {
+    /// The certificate that committed `value`.
+    pub certificate: CommitCertificate<MalachiteContext>,
+    /// The decided value itself.
+    pub value: Value,
+}
@@ -0,0 +1,236 @@
+//! Reth-backed implementation of the consensus store.
+//!
+//! Decided values are keyed by height in the `DecidedValues` table;
+//! undecided proposals are keyed by `(height, round, value_id)` in the
+//! `UndecidedProposals` table. Both are plain Reth tables reachable through
+//! whatever provider the engine was configured with, so consensus state
+//! lives in the same database as the rest of the node.
+
+use super::DecidedValue;
+use crate::{context::MalachiteContext, height::Height, Value, ValueId};
+use eyre::Result;
+use malachitebft_app_channel::app::types::ProposedValue;
+use malachitebft_core_types::{CommitCertificate, Round};
+use reth_db_api::{
+    cursor::{DbCursorRO, DbCursorRW},
+    table::TableSet,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_provider::DatabaseProviderFactory;
+use std::{ops::RangeInclusive, sync::Arc};
+
+tables! {
+    /// Decided values, keyed by height.
+    table DecidedValues<Key = u64, Value = Vec<u8>>;
+    /// Undecided proposals, keyed by `(height, round, value_id)`.
+    table UndecidedProposals<Key = (u64, i64, [u8; 32]), Value = Vec<u8>>;
+}
+
+/// Store implementation backed by Reth's database.
+pub struct RethStore<P> {
+    provider: Arc<P>,
+}
+
+impl<P> RethStore<P>
+where
+    P: DatabaseProviderFactory + Send + Sync,
+    P::Provider: Send,
+    P::ProviderRW: Send,
+{
+    /// Create a new `RethStore` over `provider`.
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Returns the maximum decided value height.
+    pub async fn max_decided_value_height(&self) -> Option<Height> {
+        let provider = self.provider.database_provider_ro().ok()?;
+        let mut cursor = provider.tx_ref().cursor_read::<DecidedValues>().ok()?;
+        let (height, _) = cursor.last().ok().flatten()?;
+        Some(Height::from(height))
+    }
+
+    /// Get a decided value by height.
+    pub async fn get_decided_value(&self, height: Height) -> Result<Option<DecidedValue>> {
+        let provider = self.provider.database_provider_ro()?;
+        let raw = provider.tx_ref().get::<DecidedValues>(height.as_u64())?;
+        raw.map(|bytes| decode_decided_value(&bytes)).transpose()
+    }
+
+    /// Store a decided value with its certificate.
+    pub async fn store_decided_value(
+        &self,
+        certificate: &CommitCertificate<MalachiteContext>,
+        value: Value,
+    ) -> Result<()> {
+        let provider = self.provider.database_provider_rw()?;
+        let decided = DecidedValue {
+            certificate: certificate.clone(),
+            value,
+        };
+        provider
+            .tx_ref()
+            .put::<DecidedValues>(certificate.height.as_u64(), encode_decided_value(&decided)?)?;
+        provider.commit()?;
+        Ok(())
+    }
+
+    /// Get undecided proposals for a height and round.
+    pub async fn get_undecided_proposals(
+        &self,
+        height: Height,
+        round: Round,
+    ) -> Result<Vec<ProposedValue<MalachiteContext>>> {
+        let provider = self.provider.database_provider_ro()?;
+        let mut cursor = provider.tx_ref().cursor_read::<UndecidedProposals>()?;
+        let start = (height.as_u64(), round.as_i64(), [0u8; 32]);
+        let end = (height.as_u64(), round.as_i64(), [0xffu8; 32]);
+
+        let mut proposals = Vec::new();
+        for entry in cursor.walk_range(start..=end)? {
+            let (_, bytes) = entry?;
+            proposals.push(decode_proposed_value(&bytes)?);
+        }
+        Ok(proposals)
+    }
+
+    /// Store an undecided proposal.
+    pub async fn store_undecided_proposal(
+        &self,
+        proposal: ProposedValue<MalachiteContext>,
+    ) -> Result<()> {
+        let provider = self.provider.database_provider_rw()?;
+        let key = (
+            proposal.height.as_u64(),
+            proposal.round.as_i64(),
+            value_id_key(&proposal.value.id()),
+        );
+        provider
+            .tx_ref()
+            .put::<UndecidedProposals>(key, encode_proposed_value(&proposal)?)?;
+        provider.commit()?;
+        Ok(())
+    }
+
+    /// Get an undecided proposal by height, round, and value ID.
+    pub async fn get_undecided_proposal(
+        &self,
+        height: Height,
+        round: Round,
+        value_id: ValueId,
+    ) -> Result<Option<ProposedValue<MalachiteContext>>> {
+        let provider = self.provider.database_provider_ro()?;
+        let key = (height.as_u64(), round.as_i64(), value_id_key(&value_id));
+        let raw = provider.tx_ref().get::<UndecidedProposals>(key)?;
+        raw.map(|bytes| decode_proposed_value(&bytes)).transpose()
+    }
+
+    /// Verify that all consensus tables exist in the database.
+    pub async fn verify_tables(&self) -> Result<()> {
+        let provider = self.provider.database_provider_ro()?;
+        provider.tx_ref().cursor_read::<DecidedValues>()?;
+        provider.tx_ref().cursor_read::<UndecidedProposals>()?;
+        Ok(())
+    }
+
+    /// Delete decided values strictly below `height` and clear undecided
+    /// proposals for heights that are already decided (`< height`).
+    ///
+    /// Returns the number of rows removed across both tables.
+    pub async fn prune_below(&self, height: Height) -> Result<u64> {
+        let provider = self.provider.database_provider_rw()?;
+        let mut removed = 0u64;
+
+        {
+            let mut cursor = provider.tx_ref().cursor_write::<DecidedValues>()?;
+            let mut walker = cursor.walk_range(..height.as_u64())?;
+            while walker.next().transpose()?.is_some() {
+                walker.delete_current()?;
+                removed += 1;
+            }
+        }
+
+        {
+            let mut cursor = provider.tx_ref().cursor_write::<UndecidedProposals>()?;
+            let mut walker = cursor.walk_range(..undecided_prune_upper_bound(height.as_u64()))?;
+            while walker.next().transpose()?.is_some() {
+                walker.delete_current()?;
+                removed += 1;
+            }
+        }
+
+        provider.commit()?;
+        Ok(removed)
+    }
+
+    /// Fetch every decided value in `range` with a single range scan instead
+    /// of one lookup per height.
+    pub async fn batch_get_decided_values(
+        &self,
+        range: RangeInclusive<Height>,
+    ) -> Result<Vec<DecidedValue>> {
+        let provider = self.provider.database_provider_ro()?;
+        let mut cursor = provider.tx_ref().cursor_read::<DecidedValues>()?;
+        let key_range = range.start().as_u64()..=range.end().as_u64();
+
+        let mut values = Vec::new();
+        for entry in cursor.walk_range(key_range)? {
+            let (_, bytes) = entry?;
+            values.push(decode_decided_value(&bytes)?);
+        }
+        Ok(values)
+    }
+}
+
+fn encode_decided_value(value: &DecidedValue) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(Into::into)
+}
+
+fn decode_decided_value(bytes: &[u8]) -> Result<DecidedValue> {
+    serde_json::from_slice(bytes).map_err(Into::into)
+}
+
+fn encode_proposed_value(value: &ProposedValue<MalachiteContext>) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(Into::into)
+}
+
+fn decode_proposed_value(bytes: &[u8]) -> Result<ProposedValue<MalachiteContext>> {
+    serde_json::from_slice(bytes).map_err(Into::into)
+}
+
+fn value_id_key(value_id: &ValueId) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    let bytes = value_id.as_bytes();
+    let len = bytes.len().min(32);
+    key[..len].copy_from_slice(&bytes[..len]);
+    key
+}
+
+/// Exclusive upper bound for deleting every `UndecidedProposals` key whose
+/// height component is strictly below `height`, regardless of round or
+/// value ID: `i64::MIN` and an all-zero value ID are the smallest possible
+/// round/value-ID components, so `(h, r, v) < (height, i64::MIN, [0; 32])`
+/// holds exactly when `h < height`.
+fn undecided_prune_upper_bound(height: u64) -> (u64, i64, [u8; 32]) {
+    (height, i64::MIN, [0u8; 32])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undecided_prune_upper_bound_excludes_current_height() {
+        let bound = undecided_prune_upper_bound(10);
+        assert!((9u64, i64::MAX, [0xffu8; 32]) < bound);
+        assert!((10u64, i64::MIN, [0u8; 32]) >= bound);
+        assert!((10u64, 0, [0u8; 32]) >= bound);
+    }
+
+    #[test]
+    fn undecided_prune_upper_bound_excludes_height_zero() {
+        let bound = undecided_prune_upper_bound(0);
+        assert!((0u64, i64::MIN, [0u8; 32]) >= bound);
+    }
+}
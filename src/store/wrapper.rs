@@ -1,12 +1,18 @@
 //! Store wrapper for easier integration with the State module.
 
 use super::RethStore;
-use crate::{context::MalachiteContext, height::Height, Value, ValueId};
+use crate::{
+    consensus::config::{CatchupConfig, RetentionConfig},
+    context::MalachiteContext,
+    height::Height,
+    Value, ValueId,
+};
 use eyre::Result;
 use malachitebft_app_channel::app::types::ProposedValue;
 use malachitebft_core_types::{CommitCertificate, Round};
+use metrics::{counter, gauge, histogram};
 use reth_provider::DatabaseProviderFactory;
-use std::sync::Arc;
+use std::{ops::RangeInclusive, sync::Arc, time::Instant};
 
 /// A wrapper around RethStore that hides the generic parameter
 #[derive(Clone)]
@@ -35,12 +41,22 @@ impl Store {
 
     /// Returns the maximum decided value height
     pub async fn max_decided_value_height(&self) -> Option<Height> {
-        self.inner.max_decided_value_height().await
+        let height = Self::timed(
+            "max_decided_value_height",
+            self.inner.max_decided_value_height(),
+        )
+        .await;
+
+        if let Some(height) = height {
+            gauge!("tempo_store_max_decided_value_height").set(height.as_u64() as f64);
+        }
+
+        height
     }
 
     /// Get a decided value by height
     pub async fn get_decided_value(&self, height: Height) -> Result<Option<super::DecidedValue>> {
-        self.inner.get_decided_value(height).await
+        Self::timed("get_decided_value", self.inner.get_decided_value(height)).await
     }
 
     /// Store a decided value with its certificate
@@ -49,7 +65,18 @@ impl Store {
         certificate: &CommitCertificate<MalachiteContext>,
         value: Value,
     ) -> Result<()> {
-        self.inner.store_decided_value(certificate, value).await
+        let height = certificate.height;
+        let result = Self::timed(
+            "store_decided_value",
+            self.inner.store_decided_value(certificate, value),
+        )
+        .await;
+
+        if result.is_ok() {
+            gauge!("tempo_store_max_decided_value_height").set(height.as_u64() as f64);
+        }
+
+        result
     }
 
     /// Get undecided proposals for a height and round
@@ -58,7 +85,11 @@ impl Store {
         height: Height,
         round: Round,
     ) -> Result<Vec<ProposedValue<MalachiteContext>>> {
-        self.inner.get_undecided_proposals(height, round).await
+        Self::timed(
+            "get_undecided_proposals",
+            self.inner.get_undecided_proposals(height, round),
+        )
+        .await
     }
 
     /// Store an undecided proposal
@@ -66,7 +97,11 @@ impl Store {
         &self,
         proposal: ProposedValue<MalachiteContext>,
     ) -> Result<()> {
-        self.inner.store_undecided_proposal(proposal).await
+        Self::timed(
+            "store_undecided_proposal",
+            self.inner.store_undecided_proposal(proposal),
+        )
+        .await
     }
 
     /// Get an undecided proposal by height, round, and value ID
@@ -76,14 +111,103 @@ impl Store {
         round: Round,
         value_id: ValueId,
     ) -> Result<Option<ProposedValue<MalachiteContext>>> {
-        self.inner
-            .get_undecided_proposal(height, round, value_id)
-            .await
+        Self::timed(
+            "get_undecided_proposal",
+            self.inner.get_undecided_proposal(height, round, value_id),
+        )
+        .await
     }
 
     /// Verify that all consensus tables exist in the database
     pub async fn verify_tables(&self) -> Result<()> {
-        self.inner.verify_tables().await
+        Self::timed("verify_tables", self.inner.verify_tables()).await
+    }
+
+    /// Get every decided value in `range`, in a single store read rather than
+    /// one round-trip per height.
+    pub async fn batch_get_decided_values(
+        &self,
+        range: RangeInclusive<Height>,
+    ) -> Result<Vec<super::DecidedValue>> {
+        Self::timed(
+            "batch_get_decided_values",
+            self.inner.batch_get_decided_values(range),
+        )
+        .await
+    }
+
+    /// Delete decided values strictly below `height` and clear undecided
+    /// proposals for heights that are already decided. Returns how many
+    /// entries were removed.
+    pub async fn prune_below(&self, height: Height) -> Result<u64> {
+        Self::timed("prune_below", self.inner.prune_below(height)).await
+    }
+
+    /// Spawn a background task that calls [`Store::prune_below`] every
+    /// `retention.prune_interval` heights, keeping at most
+    /// `retention.keep_decided_heights` of the most recent decided heights.
+    /// A `None` retention window disables pruning; the task simply tracks
+    /// height progress in that case.
+    pub fn spawn_pruning_task(&self, retention: RetentionConfig) -> tokio::task::JoinHandle<()> {
+        let store = self.clone();
+
+        tokio::spawn(async move {
+            let mut last_pruned_at: Option<u64> = None;
+
+            loop {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+                let Some(current_height) = store.max_decided_value_height().await else {
+                    continue;
+                };
+                let current_height = current_height.as_u64();
+
+                let interval = retention.prune_interval.as_u64().max(1);
+                if let Some(last) = last_pruned_at {
+                    if current_height.saturating_sub(last) < interval {
+                        continue;
+                    }
+                }
+
+                let Some(keep) = retention.keep_decided_heights else {
+                    last_pruned_at = Some(current_height);
+                    continue;
+                };
+
+                let prune_below =
+                    Height::from(current_height.saturating_add(1).saturating_sub(keep));
+                match store.prune_below(prune_below).await {
+                    Ok(pruned) => {
+                        tracing::info!(
+                            pruned,
+                            height = current_height,
+                            "pruned store below retention window"
+                        )
+                    }
+                    Err(err) => tracing::warn!(error = %err, "store pruning pass failed"),
+                }
+
+                last_pruned_at = Some(current_height);
+            }
+        })
+    }
+
+    /// Time a `StoreOps` delegation, recording a latency histogram and a call
+    /// counter labeled by operation name on the consensus engine's existing
+    /// `/metrics` server, without threading a metrics handle through
+    /// `RethStore` itself.
+    async fn timed<F, T>(op: &'static str, fut: F) -> T
+    where
+        F: std::future::Future<Output = T>,
+    {
+        let start = Instant::now();
+        let result = fut.await;
+
+        histogram!("tempo_store_op_duration_seconds", "op" => op)
+            .record(start.elapsed().as_secs_f64());
+        counter!("tempo_store_op_total", "op" => op).increment(1);
+
+        result
     }
 }
 
@@ -113,6 +237,11 @@ trait StoreOps {
         value_id: ValueId,
     ) -> Result<Option<ProposedValue<MalachiteContext>>>;
     async fn verify_tables(&self) -> Result<()>;
+    async fn prune_below(&self, height: Height) -> Result<u64>;
+    async fn batch_get_decided_values(
+        &self,
+        range: RangeInclusive<Height>,
+    ) -> Result<Vec<super::DecidedValue>>;
 }
 
 #[async_trait::async_trait]
@@ -173,4 +302,153 @@ where
     async fn verify_tables(&self) -> Result<()> {
         self.verify_tables().await.map_err(Into::into)
     }
+
+    async fn prune_below(&self, height: Height) -> Result<u64> {
+        self.prune_below(height).await.map_err(Into::into)
+    }
+
+    async fn batch_get_decided_values(
+        &self,
+        range: RangeInclusive<Height>,
+    ) -> Result<Vec<super::DecidedValue>> {
+        self.batch_get_decided_values(range)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Drives parallel catch-up sync for a node joining at a low `start_height`.
+///
+/// Coalesces missing heights into contiguous ranges and fetches them with a
+/// bounded number of concurrent [`Store::batch_get_decided_values`] calls,
+/// paced by `config.query_interval_ms`, instead of issuing one round-trip
+/// per height.
+#[derive(Clone)]
+pub struct ValueSyncDriver {
+    store: Store,
+    config: CatchupConfig,
+}
+
+impl ValueSyncDriver {
+    /// Create a new driver over `store`, tuned by `config`.
+    pub fn new(store: Store, config: CatchupConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Fetch every decided value for the given sorted, deduplicated missing
+    /// heights.
+    ///
+    /// Dispatch of each range fetch is paced by `config.query_interval_ms` in
+    /// this driving loop (a ticking interval gates every spawn), and
+    /// concurrency is bounded to `config.worker_count` in-flight requests via
+    /// a semaphore — rather than sleeping inside the fetch future itself,
+    /// which only staggers requests in bursts of `worker_count`.
+    pub async fn fetch_missing(
+        &self,
+        missing_heights: &[Height],
+    ) -> Result<Vec<super::DecidedValue>> {
+        let ranges = coalesce_contiguous(missing_heights);
+        let interval = std::time::Duration::from_millis(self.config.query_interval_ms.max(1));
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(self.config.worker_count.max(1)));
+
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let mut in_flight = tokio::task::JoinSet::new();
+        for range in ranges {
+            ticker.tick().await;
+
+            let store = self.store.clone();
+            let permit = Arc::clone(&semaphore)
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+
+            in_flight.spawn(async move {
+                let _permit = permit;
+                store.batch_get_decided_values(range).await
+            });
+        }
+
+        let mut values = Vec::new();
+        while let Some(result) = in_flight.join_next().await {
+            let batch = result.map_err(|e| eyre::eyre!("sync worker task failed: {e}"))?;
+            values.extend(batch?);
+        }
+        Ok(values)
+    }
+}
+
+/// Group strictly consecutive heights into contiguous inclusive ranges, so
+/// the sync driver can fetch each run with a single store read.
+fn coalesce_contiguous(heights: &[Height]) -> Vec<RangeInclusive<Height>> {
+    let mut ranges = Vec::new();
+    let mut iter = heights.iter().copied();
+
+    let Some(mut start) = iter.next() else {
+        return ranges;
+    };
+    let mut end = start;
+
+    for height in iter {
+        if height.as_u64() == end.as_u64() + 1 {
+            end = height;
+        } else {
+            ranges.push(start..=end);
+            start = height;
+            end = height;
+        }
+    }
+    ranges.push(start..=end);
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heights(values: &[u64]) -> Vec<Height> {
+        values.iter().copied().map(Height::from).collect()
+    }
+
+    fn bounds(ranges: &[RangeInclusive<Height>]) -> Vec<(u64, u64)> {
+        ranges
+            .iter()
+            .map(|r| (r.start().as_u64(), r.end().as_u64()))
+            .collect()
+    }
+
+    #[test]
+    fn coalesce_contiguous_empty_input_yields_no_ranges() {
+        assert!(coalesce_contiguous(&[]).is_empty());
+    }
+
+    #[test]
+    fn coalesce_contiguous_single_height_is_its_own_range() {
+        let ranges = coalesce_contiguous(&heights(&[5]));
+        assert_eq!(bounds(&ranges), vec![(5, 5)]);
+    }
+
+    #[test]
+    fn coalesce_contiguous_merges_consecutive_runs() {
+        let ranges = coalesce_contiguous(&heights(&[1, 2, 3, 5, 6, 9]));
+        assert_eq!(bounds(&ranges), vec![(1, 3), (5, 6), (9, 9)]);
+    }
+
+    #[test]
+    fn coalesce_contiguous_treats_a_repeated_height_as_its_own_successor() {
+        // A repeated `2` isn't `end + 1` of itself, so it closes the
+        // current range and starts the next one from that same height.
+        let ranges = coalesce_contiguous(&heights(&[1, 2, 2, 3]));
+        assert_eq!(bounds(&ranges), vec![(1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn coalesce_contiguous_treats_a_decrease_as_a_gap() {
+        // Input isn't required to be sorted: a height smaller than the
+        // running end is never `end + 1`, so it always starts a new range.
+        let ranges = coalesce_contiguous(&heights(&[3, 4, 1, 2]));
+        assert_eq!(bounds(&ranges), vec![(3, 4), (1, 2)]);
+    }
 }